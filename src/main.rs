@@ -1,81 +1,159 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
 #[derive(Debug, Clone)]
 enum Token {
     Number(i64),
+    Identifier(String),
+    Let,
+    Equals,
     Plus,
     Dash,
     Star,
     Slash,
+    Caret,
+    Pipe,
     LeftParen,
     RightParen,
     EOF,
 }
 
+//Binding power of each operator token; higher binds tighter. None for non-operator tokens.
+fn precedence(token: &Token) -> Option<u8> {
+    match token {
+        Token::Plus | Token::Dash => Some(1),
+        Token::Star | Token::Slash => Some(2),
+        Token::Caret => Some(3),
+        _ => None,
+    }
+}
+
+//Structured diagnostics carrying the position of the offense, so a caller can point
+//at the offending column instead of just reading a flat message.
 #[derive(Debug)]
-struct SyntaxError {
-    message: String,
+enum CompileError {
+    UnrecognizedChar { ch: char, pos: usize },
+    UnexpectedToken { found: Token, pos: usize },
+    ExpectedClosingParen { pos: usize },
+    UnexpectedEof,
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CompileError::UnrecognizedChar { ch, .. } => {
+                write!(f, "unrecognized character '{}'", ch)
+            }
+            CompileError::UnexpectedToken { found, .. } => {
+                write!(f, "unexpected token: {:?}", found)
+            }
+            CompileError::ExpectedClosingParen { .. } => write!(f, "expected ')'"),
+            CompileError::UnexpectedEof => write!(f, "unexpected end of input"),
+        }
+    }
 }
 
-impl SyntaxError { 
-    fn new(message: String) -> Self {
-        SyntaxError {
-            message,
+impl CompileError {
+    //Char offset of the offending position, if one is known, for pointing a caret at `source`.
+    fn pos(&self) -> Option<usize> {
+        match self {
+            CompileError::UnrecognizedChar { pos, .. } => Some(*pos),
+            CompileError::UnexpectedToken { pos, .. } => Some(*pos),
+            CompileError::ExpectedClosingParen { pos } => Some(*pos),
+            CompileError::UnexpectedEof => None,
         }
     }
 }
 
-fn tokenizer(input: String) -> Result<Vec<Token>, SyntaxError> {
-    let mut tokens: Vec<Token> = Vec::new();
-    let mut chars = input.chars().peekable(); // Peekable iterator to look ahead without consuming
+//Renders `err` against the `source` line it came from: the message, then the source
+//line, then a caret under the offending column so the reader can see exactly where
+//the error is, not just how far into the token/char stream it occurred.
+fn render_compile_error(err: &CompileError, source: &str) -> String {
+    match err.pos() {
+        Some(pos) => format!("{}\n{}\n{}^", err, source, " ".repeat(pos)),
+        None => err.to_string(),
+    }
+}
+
+//Each token is tagged with the char offset it started at, so the parser can point
+//a caret at real source columns instead of counting tokens.
+fn tokenizer(input: String) -> Result<Vec<(Token, usize)>, CompileError> {
+    let mut tokens: Vec<(Token, usize)> = Vec::new();
+    let chars: Vec<char> = input.chars().collect();
+    let mut pos = 0;
 
-    while let Some(&ch) = chars.peek() {
+    while pos < chars.len() {
+        let ch = chars[pos];
+        let start = pos;
         match ch {
             ch if ch.is_whitespace() => {
-                chars.next(); 
+                pos += 1;
             }
             '(' => {
-                tokens.push(Token::LeftParen);
-                chars.next();
+                tokens.push((Token::LeftParen, start));
+                pos += 1;
             }
             ')' => {
-                tokens.push(Token::RightParen);
-                chars.next();
+                tokens.push((Token::RightParen, start));
+                pos += 1;
             }
             '+' => {
-                tokens.push(Token::Plus);
-                chars.next(); 
+                tokens.push((Token::Plus, start));
+                pos += 1;
             }
             '-' => {
-                tokens.push(Token::Dash);
-                chars.next(); 
+                tokens.push((Token::Dash, start));
+                pos += 1;
             }
             '*' => {
-                tokens.push(Token::Star);
-                chars.next(); 
+                tokens.push((Token::Star, start));
+                pos += 1;
             }
             '/' => {
-                tokens.push(Token::Slash);
-                chars.next(); 
+                tokens.push((Token::Slash, start));
+                pos += 1;
+            }
+            '^' => {
+                tokens.push((Token::Caret, start));
+                pos += 1;
+            }
+            '|' => {
+                tokens.push((Token::Pipe, start));
+                pos += 1;
+            }
+            '=' => {
+                tokens.push((Token::Equals, start));
+                pos += 1;
+            }
+            ch if ch.is_alphabetic() || ch == '_' => {
+                let mut ident = String::new();
+                while pos < chars.len() && (chars[pos].is_alphanumeric() || chars[pos] == '_') {
+                    ident.push(chars[pos]);
+                    pos += 1;
+                }
+                if ident == "let" {
+                    tokens.push((Token::Let, start));
+                } else {
+                    tokens.push((Token::Identifier(ident), start));
+                }
             }
             '0'..='9' => {
                 let mut number = String::new();
-                while let Some(&digit) = chars.peek() {
-                    if digit.is_ascii_digit() {
-                        number.push(digit);
-                        chars.next(); // Advance the iterator manually for each digit
-                    } else {
-                        break;
-                    }
+                while pos < chars.len() && chars[pos].is_ascii_digit() {
+                    number.push(chars[pos]);
+                    pos += 1;
                 }
                 let value: i64 = number.parse::<i64>().unwrap();
-                tokens.push(Token::Number(value));
+                tokens.push((Token::Number(value), start));
             }
             _ => {
-                return Err(SyntaxError::new(format!("unrecognized character {}", ch)));
+                return Err(CompileError::UnrecognizedChar { ch, pos });
             }
         }
     }
 
-    tokens.push(Token::EOF);
+    tokens.push((Token::EOF, pos));
     Ok(tokens)
 }
 
@@ -86,128 +164,398 @@ fn tokenizer(input: String) -> Result<Vec<Token>, SyntaxError> {
 #[derive(Debug)]
 enum ASTNode {
     Number(i64),
+    Variable(String),
+    Assignment {
+        name: String,
+        value: Box<ASTNode>,
+    },
     BinaryOp {
         op: Token,
         left: Box<ASTNode>,
         right: Box<ASTNode>,
     },
+    UnaryOp {
+        op: Token,
+        operand: Box<ASTNode>,
+    },
+}
+
+#[derive(Debug)]
+enum EvalError {
+    DivisionByZero,
+    NegativeExponent,
+    Overflow,
+    UnboundVariable(String),
+}
+
+//Walks the AST bottom-up, folding each BinaryOp's children into a single value.
+//`env` carries variables bound by `let` so later expressions can resolve them.
+//All arithmetic goes through checked_* so an overflowing expression reports
+//EvalError::Overflow instead of panicking and taking the REPL down with it.
+fn eval(node: &ASTNode, env: &mut HashMap<String, i64>) -> Result<i64, EvalError> {
+    match node {
+        ASTNode::Number(value) => Ok(*value),
+        ASTNode::Variable(name) => env
+            .get(name)
+            .copied()
+            .ok_or_else(|| EvalError::UnboundVariable(name.clone())),
+        ASTNode::Assignment { name, value } => {
+            let value = eval(value, env)?;
+            env.insert(name.clone(), value);
+            Ok(value)
+        }
+        ASTNode::BinaryOp { op, left, right } => {
+            let left = eval(left, env)?;
+            let right = eval(right, env)?;
+            match op {
+                Token::Plus => left.checked_add(right).ok_or(EvalError::Overflow),
+                Token::Dash => left.checked_sub(right).ok_or(EvalError::Overflow),
+                Token::Star => left.checked_mul(right).ok_or(EvalError::Overflow),
+                Token::Slash => {
+                    if right == 0 {
+                        Err(EvalError::DivisionByZero)
+                    } else {
+                        left.checked_div(right).ok_or(EvalError::Overflow)
+                    }
+                }
+                Token::Caret => {
+                    if right < 0 {
+                        Err(EvalError::NegativeExponent)
+                    } else {
+                        let exponent: u32 = right.try_into().map_err(|_| EvalError::Overflow)?;
+                        left.checked_pow(exponent).ok_or(EvalError::Overflow)
+                    }
+                }
+                _ => unreachable!("BinaryOp can only carry an arithmetic operator token"),
+            }
+        }
+        ASTNode::UnaryOp { op, operand } => {
+            let operand = eval(operand, env)?;
+            match op {
+                Token::Dash => operand.checked_neg().ok_or(EvalError::Overflow),
+                Token::Plus => Ok(operand),
+                Token::Pipe => operand.checked_abs().ok_or(EvalError::Overflow),
+                _ => unreachable!("UnaryOp can only carry a unary operator token"),
+            }
+        }
+    }
 }
 
 struct Parser {
-    tokens: Vec<Token>,
+    tokens: Vec<(Token, usize)>,
     current: usize,
 }
 
 impl Parser {
-    fn new(tokens: Vec<Token>) -> Self {
+    fn new(tokens: Vec<(Token, usize)>) -> Self {
         // -> Self means return an instance of same type
         Parser { tokens, current: 0 }
     }
 
     //Check current Token Without Advancing
     fn peek(&self) -> Option<&Token> {
-        let token = self.tokens.get(self.current);
-        println!("Peeking: {:?} -> {:?}", self.current, token);
-        token
+        self.tokens.get(self.current).map(|(token, _)| token)
     }
     //Go to next token, add one to current index
     fn advance(&mut self) -> Option<Token> {
-        let token = self.tokens.get(self.current).cloned();
-        println!("Advancing: {:?} -> {:?}", self.current, token);
+        let token = self.tokens.get(self.current).map(|(token, _)| token.clone());
         self.current += 1;
         token
     }
 
+    //Source offset the token at `idx` started at, for pointing error carets at real columns
+    //rather than a raw token count.
+    fn pos_at(&self, idx: usize) -> usize {
+        self.tokens
+            .get(idx)
+            .or_else(|| self.tokens.last())
+            .map(|(_, pos)| *pos)
+            .unwrap_or(0)
+    }
+
     //IF number return ASTNode with the number if keft paren parse the subexpression and check for right paren
-    fn parse_factor(&mut self) -> Result<ASTNode, SyntaxError> {
+    fn parse_factor(&mut self) -> Result<ASTNode, CompileError> {
         match self.advance() {
             Some(Token::Number(value)) => {
-                println!("Parsed Number: {}", value);
                 Ok(ASTNode::Number(value)) // Return a Number node
             }
+            Some(Token::Identifier(name)) => Ok(ASTNode::Variable(name)),
+            Some(op @ Token::Dash) | Some(op @ Token::Plus) => {
+                let operand = self.parse_factor()?;
+                Ok(ASTNode::UnaryOp { op, operand: Box::new(operand) })
+            }
+            Some(Token::Pipe) => {
+                let node = self.parse_expr(1)?; // Parse the enclosed subexpression
+
+                match self.peek() {
+                    Some(Token::Pipe) => {
+                        self.advance(); // Consume the closing Pipe
+                        Ok(ASTNode::UnaryOp { op: Token::Pipe, operand: Box::new(node) })
+                    }
+                    Some(token) => Err(CompileError::UnexpectedToken {
+                        found: token.clone(),
+                        pos: self.pos_at(self.current),
+                    }),
+                    None => Err(CompileError::UnexpectedEof),
+                }
+            }
             Some(Token::LeftParen) => {
-                println!("Parsing subexpression inside parentheses");
                 // Recursively parse the subexpression
-                let node = self.parse_expression()?; // Parse the subexpression
-                
+                let node = self.parse_expr(1)?; // Parse the subexpression
+
                 // Check for matching RightParen after parsing the subexpression
                 if let Some(Token::RightParen) = self.peek() {
                     self.advance(); // Consume the RightParen
-                    println!("Matched closing parenthesis");
                     Ok(node) // Successfully parsed and matched parentheses
                 } else {
-                    Err(SyntaxError::new(format!(
-                        "Expected ')', but found {:?}",
-                        self.peek()
-                    )))
+                    Err(CompileError::ExpectedClosingParen { pos: self.pos_at(self.current) })
                 }
             }
-            Some(token) => Err(SyntaxError::new(format!("Unexpected token: {:?}", token))),
-            None => Err(SyntaxError::new("Unexpected end of input".to_string())),
+            Some(token) => Err(CompileError::UnexpectedToken {
+                found: token,
+                pos: self.pos_at(self.current - 1),
+            }),
+            None => Err(CompileError::UnexpectedEof),
         }
     }
-    
-    
-    
-    
-    
-    
 
-    fn parse_term(&mut self) -> Result<ASTNode, SyntaxError> {
+    //Precedence climbing: parse a factor, then keep folding in operators whose
+    //binding power is at least min_prec, recursing with a higher floor for the
+    //right-hand side of left-associative operators so they don't re-absorb
+    //same-precedence operators to their right.
+    fn parse_expr(&mut self, min_prec: u8) -> Result<ASTNode, CompileError> {
         let mut node = self.parse_factor()?;
 
         while let Some(token) = self.peek() {
-            match token {
-                Token::Star | Token::Slash => {
-                    let op = self.advance().expect("Expected operator but found unexpected EOF");
-                    let right = self.parse_factor()?;
-                    node = ASTNode::BinaryOp { op, left: Box::new(node), right: Box::new(right) };
-                }
-                _ => {
-                    break;
-                }
-            }
+            let op_prec = match precedence(token) {
+                Some(prec) if prec >= min_prec => prec,
+                _ => break,
+            };
+            let op = self.advance().expect("Expected operator but found unexpected EOF");
+            let next_min_prec = if matches!(op, Token::Caret) {
+                op_prec // right-associative: allow the same precedence to recurse
+            } else {
+                op_prec + 1 // left-associative: require strictly higher precedence
+            };
+            let right = self.parse_expr(next_min_prec)?;
+            node = ASTNode::BinaryOp { op, left: Box::new(node), right: Box::new(right) };
         }
         Ok(node)
     }
 
-    fn parse_expression(&mut self) -> Result<ASTNode, SyntaxError> {
-        let mut node = self.parse_term()?;
-        while let Some(token) = self.peek() {
-            match token {
-                Token::Plus | Token::Dash => {
-                    let op = self.advance().expect("Expected operator but found unexpected EOF");
-                    let right = self.parse_term()?;
-                    node = ASTNode::BinaryOp {          
-                        op,
-                        left: Box::new(node),
-                        right: Box::new(right),
-                    };
-                }
-                _ => break, // Stop processing if no matching operator is found
+    //Top-level `let <name> = <expr>` statement form, tried ahead of a plain expression.
+    fn parse_let_statement(&mut self) -> Result<ASTNode, CompileError> {
+        self.advance(); // Consume the Let keyword
+
+        let name = match self.advance() {
+            Some(Token::Identifier(name)) => name,
+            Some(token) => {
+                return Err(CompileError::UnexpectedToken {
+                    found: token,
+                    pos: self.pos_at(self.current - 1),
+                })
             }
+            None => return Err(CompileError::UnexpectedEof),
+        };
+
+        match self.advance() {
+            Some(Token::Equals) => {}
+            Some(token) => {
+                return Err(CompileError::UnexpectedToken {
+                    found: token,
+                    pos: self.pos_at(self.current - 1),
+                })
+            }
+            None => return Err(CompileError::UnexpectedEof),
         }
-        Ok(node)
+
+        let value = self.parse_expr(1)?;
+        Ok(ASTNode::Assignment { name, value: Box::new(value) })
+    }
+
+    fn parse(&mut self) -> Result<ASTNode, CompileError> {
+        if let Some(Token::Let) = self.peek() {
+            self.parse_let_statement()
+        } else {
+            self.parse_expr(1)
+        }
+    }
+}
+
+//Which compilation stage the REPL prints for each line of input
+#[derive(Debug, Clone, Copy)]
+enum Mode {
+    Tokens,
+    Ast,
+    Eval,
+}
+
+//Runs one line through the tokenizer/parser and prints whatever `mode` asks for,
+//reporting errors from either phase without aborting the loop.
+fn run_line(line: &str, mode: Mode, env: &mut HashMap<String, i64>) {
+    let tokens = match tokenizer(line.to_string()) {
+        Ok(tokens) => tokens,
+        Err(err) => {
+            eprintln!("Tokenization Error: {}", render_compile_error(&err, line));
+            return;
+        }
+    };
+
+    if let Mode::Tokens = mode {
+        println!("Tokens: {:?}", tokens);
+        return;
     }
-    
-    
 
-    fn parse(&mut self) -> Result<ASTNode, SyntaxError> {
-        self.parse_expression()
+    let mut parser = Parser::new(tokens);
+    let ast = match parser.parse() {
+        Ok(ast) => ast,
+        Err(err) => {
+            eprintln!("Parse Error: {}", render_compile_error(&err, line));
+            return;
+        }
+    };
+
+    match mode {
+        Mode::Ast => println!("AST: {:#?}", ast),
+        Mode::Eval => match eval(&ast, env) {
+            Ok(result) => println!("Result: {}", result),
+            Err(err) => eprintln!("Evaluation Error: {:?}", err),
+        },
+        Mode::Tokens => unreachable!("handled above"),
     }
 }
 
 fn main() {
-    let input = "3 + 5 * (((10 - 2)))".to_string();
-    match tokenizer(input) {
-        Ok(tokens) => {
-            println!("Tokens: {:?}", tokens);
-            let mut parser = Parser::new(tokens);
-            match parser.parse() {
-                Ok(ast) => println!("AST: {:#?}", ast),
-                Err(err) => eprintln!("Parse Error: {}", err.message),
+    let stdin = io::stdin();
+    let mut mode = Mode::Eval;
+    let mut env: HashMap<String, i64> = HashMap::new();
+
+    print!("> ");
+    io::stdout().flush().unwrap();
+    for line in stdin.lock().lines() {
+        let line = line.expect("Failed to read line from stdin");
+        match line.trim() {
+            "" => {}
+            ":tokens" => {
+                mode = Mode::Tokens;
+                println!("Mode set to tokens");
+            }
+            ":ast" => {
+                mode = Mode::Ast;
+                println!("Mode set to ast");
             }
+            ":eval" => {
+                mode = Mode::Eval;
+                println!("Mode set to eval");
+            }
+            ":quit" | ":exit" => break,
+            input => run_line(input, mode, &mut env),
         }
-        Err(err) => eprintln!("Tokenization Error: {}", err.message),
+        print!("> ");
+        io::stdout().flush().unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //Tokenizes, parses, and evaluates `input` against a fresh environment.
+    fn eval_input(input: &str) -> Result<i64, EvalError> {
+        let tokens = tokenizer(input.to_string()).expect("tokenizer should succeed");
+        let ast = Parser::new(tokens).parse().expect("parse should succeed");
+        let mut env = HashMap::new();
+        eval(&ast, &mut env)
+    }
+
+    #[test]
+    fn evaluates_basic_arithmetic() {
+        assert_eq!(eval_input("3 + 5 * 2").unwrap(), 13);
+    }
+
+    #[test]
+    fn division_by_zero_is_an_error() {
+        assert!(matches!(eval_input("1 / 0"), Err(EvalError::DivisionByZero)));
+    }
+
+    #[test]
+    fn multiplication_binds_tighter_than_addition() {
+        assert_eq!(eval_input("2 + 3 * 4").unwrap(), 14);
+    }
+
+    #[test]
+    fn exponentiation_is_right_associative() {
+        assert_eq!(eval_input("2 ^ 3 ^ 2").unwrap(), 512); // 2^(3^2), not (2^3)^2
+    }
+
+    #[test]
+    fn negative_exponent_is_an_error() {
+        assert!(matches!(eval_input("2 ^ -1"), Err(EvalError::NegativeExponent)));
+    }
+
+    #[test]
+    fn overflowing_arithmetic_is_an_error_instead_of_a_panic() {
+        assert!(matches!(eval_input("2 ^ 100"), Err(EvalError::Overflow)));
+        assert!(matches!(
+            eval_input("999999999999 * 999999999999"),
+            Err(EvalError::Overflow)
+        ));
+    }
+
+    #[test]
+    fn unary_minus_negates() {
+        assert_eq!(eval_input("-5").unwrap(), -5);
+    }
+
+    #[test]
+    fn unary_plus_is_identity() {
+        assert_eq!(eval_input("+5").unwrap(), 5);
+    }
+
+    #[test]
+    fn absolute_value_of_negative() {
+        assert_eq!(eval_input("|-5|").unwrap(), 5);
+    }
+
+    #[test]
+    fn let_binding_is_visible_to_later_expressions() {
+        let mut env = HashMap::new();
+
+        let tokens = tokenizer("let x = 5".to_string()).expect("tokenizer should succeed");
+        let ast = Parser::new(tokens).parse().expect("parse should succeed");
+        eval(&ast, &mut env).expect("let binding should evaluate");
+
+        let tokens = tokenizer("x + 1".to_string()).expect("tokenizer should succeed");
+        let ast = Parser::new(tokens).parse().expect("parse should succeed");
+        assert_eq!(eval(&ast, &mut env).unwrap(), 6);
+    }
+
+    #[test]
+    fn unbound_variable_is_an_error() {
+        match eval_input("y") {
+            Err(EvalError::UnboundVariable(name)) => assert_eq!(name, "y"),
+            other => panic!("expected UnboundVariable, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rendered_error_points_the_caret_under_the_offending_column() {
+        let source = "3 + #";
+        let err = tokenizer(source.to_string()).unwrap_err();
+        assert_eq!(
+            render_compile_error(&err, source),
+            "unrecognized character '#'\n3 + #\n    ^"
+        );
+    }
+
+    #[test]
+    fn rendered_parse_error_points_the_caret_where_the_closing_paren_is_missing() {
+        let source = "(100 + 200";
+        let tokens = tokenizer(source.to_string()).unwrap();
+        let err = Parser::new(tokens).parse().unwrap_err();
+        assert_eq!(
+            render_compile_error(&err, source),
+            "expected ')'\n(100 + 200\n          ^"
+        );
     }
 }